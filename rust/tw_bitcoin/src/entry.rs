@@ -1,7 +1,7 @@
 use crate::{Error, Result};
 use bitcoin::address::NetworkChecked;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Display;
 use std::str::FromStr;
 use tw_coin_entry::coin_context::CoinContext;
@@ -10,12 +10,14 @@ use tw_coin_entry::derivation::Derivation;
 use tw_coin_entry::error::{AddressError, AddressResult};
 use tw_coin_entry::modules::json_signer::NoJsonSigner;
 use tw_coin_entry::modules::plan_builder::NoPlanBuilder;
-use tw_coin_entry::prefix::NoPrefix;
 use tw_keypair::tw::PublicKey;
 use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
 use tw_proto::Utxo::Proto as UtxoProto;
 
+pub use crate::modules::htlc::HtlcClaim;
+pub use crate::modules::prefix::{BitcoinAddressPrefix, BitcoinAddressType, BitcoinNetwork};
+
 pub struct Address(pub bitcoin::address::Address<NetworkChecked>);
 
 impl Display for Address {
@@ -33,7 +35,7 @@ impl CoinAddress for Address {
 pub struct BitcoinEntry;
 
 impl CoinEntry for BitcoinEntry {
-    type AddressPrefix = NoPrefix;
+    type AddressPrefix = BitcoinAddressPrefix;
     type Address = Address;
     type SigningInput<'a> = Proto::SigningInput<'a>;
     type SigningOutput = Proto::SigningOutput<'static>;
@@ -48,11 +50,13 @@ impl CoinEntry for BitcoinEntry {
         &self,
         _coin: &dyn CoinContext,
         address: &str,
-        _prefix: Option<Self::AddressPrefix>,
+        prefix: Option<Self::AddressPrefix>,
     ) -> AddressResult<Self::Address> {
+        let network = prefix.unwrap_or_default().network.into();
+
         let address = bitcoin::address::Address::from_str(address)
             .map_err(|_| AddressError::FromHexError)?
-            .require_network(bitcoin::Network::Bitcoin)
+            .require_network(network)
             .map_err(|_| AddressError::InvalidInput)?;
 
         Ok(Address(address))
@@ -64,8 +68,11 @@ impl CoinEntry for BitcoinEntry {
         _coin: &dyn CoinContext,
         public_key: PublicKey,
         _derivation: Derivation,
-        _prefix: Option<Self::AddressPrefix>,
+        prefix: Option<Self::AddressPrefix>,
     ) -> AddressResult<Self::Address> {
+        let prefix = prefix.unwrap_or_default();
+        let network = prefix.network.into();
+
         let pubkey = match public_key {
             PublicKey::Secp256k1(pubkey) | PublicKey::Secp256k1Extended(pubkey) => pubkey,
             _ => return Err(AddressError::InvalidInput),
@@ -74,10 +81,28 @@ impl CoinEntry for BitcoinEntry {
         let pubkey = bitcoin::PublicKey::from_slice(pubkey.to_vec().as_ref())
             .map_err(|_| AddressError::InvalidInput)?;
 
-        let address: bitcoin::address::Address<NetworkChecked> = bitcoin::address::Address::new(
-            bitcoin::Network::Bitcoin,
-            bitcoin::address::Payload::PubkeyHash(pubkey.pubkey_hash()),
-        );
+        let address: bitcoin::address::Address<NetworkChecked> = match prefix.address_type {
+            BitcoinAddressType::P2pkh => bitcoin::address::Address::new(
+                network,
+                bitcoin::address::Payload::PubkeyHash(pubkey.pubkey_hash()),
+            ),
+            BitcoinAddressType::P2wpkh => bitcoin::address::Address::p2wpkh(&pubkey, network)
+                // A P2WPKH address requires a compressed key; reject uncompressed ones
+                // rather than silently falling back to a different script type.
+                .map_err(|_| AddressError::InvalidInput)?,
+            BitcoinAddressType::P2shP2wpkh => {
+                bitcoin::address::Address::p2shwpkh(&pubkey, network)
+                    .map_err(|_| AddressError::InvalidInput)?
+            },
+            BitcoinAddressType::P2tr => {
+                // BIP86 key-path spend: tweak the internal (x-only) key with an
+                // empty merkle root, matching the script types the signer can
+                // already spend from.
+                let secp = bitcoin::secp256k1::Secp256k1::verification_only();
+                let internal_key = bitcoin::key::XOnlyPublicKey::from(pubkey.inner);
+                bitcoin::address::Address::p2tr(&secp, internal_key, None, network)
+            },
+        };
 
         Ok(Address(address))
     }
@@ -116,9 +141,9 @@ impl CoinEntry for BitcoinEntry {
         _coin: &dyn CoinContext,
         proto: Proto::SigningInput<'_>,
         signatures: Vec<SignatureBytes>,
-        _public_keys: Vec<PublicKeyBytes>,
+        public_keys: Vec<PublicKeyBytes>,
     ) -> Self::SigningOutput {
-        self.compile_impl(_coin, proto, signatures, _public_keys)
+        self.compile_impl(_coin, proto, signatures, public_keys)
             .or_else(|err| {
                 std::result::Result::<_, ()>::Ok(Proto::SigningOutput {
                     error: err.into(),
@@ -163,14 +188,24 @@ impl BitcoinEntry {
 
         // Sign the sighashes.
         let signatures = crate::modules::signer::Signer::signatures_from_proto(
-            &pre_signed,
+            &pre_signed.sighashes,
             proto.private_key.to_vec(),
             individual_keys,
             proto.dangerous_use_fixed_schnorr_rng,
         )?;
 
+        // Each signature lines up with `pre_signed.sighashes`, which already
+        // carries the public key it was produced for; forward that pairing
+        // to `compile_impl` so it can match by key the same way it would for
+        // signatures that came back from an external/hardware signer.
+        let public_keys = pre_signed
+            .sighashes
+            .iter()
+            .map(|sighash| sighash.public_key.to_vec())
+            .collect();
+
         // Construct the final transaction.
-        self.compile_impl(_coin, proto, signatures, vec![])
+        self.compile_impl(_coin, proto, signatures, public_keys)
     }
 
     fn preimage_hashes_impl(
@@ -181,7 +216,10 @@ impl BitcoinEntry {
         // Convert input builders into Utxo inputs.
         let mut utxo_inputs = vec![];
         for input in proto.inputs {
-            let txin = crate::modules::transactions::InputBuilder::utxo_from_proto(&input)?;
+            let txin = crate::modules::transactions::InputBuilder::utxo_from_proto(
+                &input,
+                proto.lock_time,
+            )?;
             utxo_inputs.push(txin);
         }
 
@@ -228,9 +266,32 @@ impl BitcoinEntry {
         let utxo_presigning = tw_utxo::compiler::Compiler::preimage_hashes(utxo_signing);
         handle_utxo_error(&utxo_presigning.error)?;
 
+        // Pair each sighash with the key/derivation that is expected to sign
+        // it, so an air-gapped/hardware signer can be told *which* key and
+        // BIP32 path a given hash belongs to without ever seeing a private
+        // key. `utxo_presigning.inputs` is index-aligned with the sighashes.
+        let sighashes = utxo_presigning
+            .sighashes
+            .into_iter()
+            .zip(utxo_presigning.inputs.iter())
+            .enumerate()
+            .map(|(input_index, (sighash, utxo_input))| {
+                let (public_key, derivation_path) =
+                    crate::modules::transactions::InputBuilder::expected_signing_key(utxo_input);
+
+                Proto::InputSighash {
+                    input_index: input_index as u32,
+                    sighash,
+                    sighash_type: utxo_input.sighash_type,
+                    public_key: Cow::Owned(public_key),
+                    derivation_path: derivation_path.map(Cow::Owned).unwrap_or_default(),
+                }
+            })
+            .collect();
+
         Ok(Proto::PreSigningOutput {
             error: Proto::Error::OK,
-            sighashes: utxo_presigning.sighashes,
+            sighashes,
             // Update selected inputs.
             utxo_inputs: utxo_presigning.inputs,
             utxo_outputs,
@@ -244,25 +305,63 @@ impl BitcoinEntry {
         _coin: &dyn CoinContext,
         proto: Proto::SigningInput<'_>,
         signatures: Vec<SignatureBytes>,
-        _public_keys: Vec<PublicKeyBytes>,
+        public_keys: Vec<PublicKeyBytes>,
     ) -> Result<Proto::SigningOutput<'static>> {
-        // There must be a signature for each input.
-        if proto.inputs.len() != signatures.len() {
+        // `signatures`/`public_keys` are matched by key, not position: an
+        // air-gapped/hardware signer is free to hand them back in whatever
+        // order it signed in, not necessarily the order `proto.inputs` were
+        // declared in. Group them by public key first, preserving each key's
+        // own relative order, so two inputs sharing a signing key - an
+        // ordinary multi-UTXO wallet scenario - each get the signature
+        // produced for *their* sighash instead of colliding into one lookup;
+        // this only requires that entries for the same key arrive in the
+        // same relative order as the inputs that need them, which is what
+        // a signer working sighash-by-sighash naturally produces anyway.
+        if signatures.len() != public_keys.len() {
             return Err(Error::from(
                 Proto::Error::Error_unmatched_input_signature_count,
             ));
         }
 
-        // Generate claims for all the inputs.
+        let mut signatures_by_pubkey: HashMap<&[u8], VecDeque<&[u8]>> = HashMap::new();
+        for (public_key, signature) in public_keys.iter().zip(signatures.iter()) {
+            signatures_by_pubkey
+                .entry(public_key.as_ref())
+                .or_default()
+                .push_back(signature.as_ref());
+        }
+
+        // Generate claims for all the inputs, looking up each one's
+        // signature by its expected public key.
         let mut utxo_input_claims: Vec<UtxoProto::TxInClaim> = vec![];
-        for (input, signature) in proto.inputs.iter().zip(signatures.into_iter()) {
+        for input in proto.inputs.iter() {
+            let expected_pubkey =
+                crate::modules::transactions::InputClaimBuilder::expected_public_key(input)?;
+
+            let signature = signatures_by_pubkey
+                .get_mut(expected_pubkey.as_slice())
+                .and_then(VecDeque::pop_front)
+                .ok_or_else(|| Error::from(Proto::Error::Error_missing_input_signature))?
+                .to_vec();
+
             let utxo_claim =
                 crate::modules::transactions::InputClaimBuilder::utxo_claim_from_proto(
-                    input, signature,
+                    input,
+                    signature,
+                    proto.lock_time,
                 )?;
             utxo_input_claims.push(utxo_claim);
         }
 
+        // Every supplied signature must have been claimed by some input;
+        // leftovers mean the caller handed back more signatures than there
+        // were inputs asking for that key.
+        if signatures_by_pubkey.values().any(|queue| !queue.is_empty()) {
+            return Err(Error::from(
+                Proto::Error::Error_unmatched_input_signature_count,
+            ));
+        }
+
         // Process all the outputs.
         let mut utxo_outputs = vec![];
         for output in proto.outputs {
@@ -338,6 +437,208 @@ impl BitcoinEntry {
     }
 }
 
+impl BitcoinEntry {
+    /// BIP-174 "Updater"+"Signer" role: take a PSBT, sign every input this
+    /// entry holds a private key for, and return the merged PSBT.
+    ///
+    /// Inputs that can't be signed (no matching key) are left untouched so
+    /// the PSBT can keep travelling between co-signers, mirroring the
+    /// Creator/Updater/Signer/Finalizer split from BIP-174. This is the PSBT
+    /// counterpart to `sign_impl`, but it never calls `compile_impl` itself:
+    /// finalizing into a spendable transaction is a separate, explicit step
+    /// (`psbt_finalize`).
+    pub fn psbt_sign(
+        &self,
+        coin: &dyn CoinContext,
+        proto: Proto::PsbtSigningInput<'_>,
+    ) -> Result<Proto::PsbtSigningOutput<'static>> {
+        let mut psbt = crate::modules::psbt::deserialize(&proto.psbt)?;
+
+        let pre_signed = self.psbt_preimage_hashes(coin, proto.clone())?;
+        if pre_signed.error != Proto::Error::OK {
+            return Err(Error::from(pre_signed.error));
+        }
+
+        let mut individual_keys = HashMap::new();
+        for (index, key) in proto.private_keys.iter().enumerate() {
+            if !key.is_empty() {
+                individual_keys.insert(index, key.to_vec());
+            }
+        }
+
+        let signatures = crate::modules::signer::Signer::try_signatures_from_proto(
+            &pre_signed.sighashes,
+            Vec::default(),
+            individual_keys,
+            proto.dangerous_use_fixed_schnorr_rng,
+        )?;
+
+        // Only the inputs this call actually holds a key for get a signature;
+        // an unsignable input (no `individual_keys` entry, the ordinary
+        // "this co-signer's turn hasn't come yet" case) is simply skipped so
+        // the PSBT keeps carrying it to the next signer, rather than failing
+        // the whole batch over one input nobody asked this call to sign.
+        let keyed_signatures: Vec<(u32, Vec<u8>, Vec<u8>)> = pre_signed
+            .sighashes
+            .iter()
+            .zip(signatures.into_iter())
+            .filter_map(|(presig, sig)| sig.map(|sig| (presig.input_index, presig.public_key.to_vec(), sig)))
+            .collect();
+
+        crate::modules::psbt::apply_partial_signatures(&mut psbt, &keyed_signatures)?;
+
+        Ok(Proto::PsbtSigningOutput {
+            error: Proto::Error::OK,
+            psbt: Cow::Owned(psbt.serialize()),
+        })
+    }
+
+    /// Convert every signable PSBT input into a sighash, the same way
+    /// `preimage_hashes_impl` does for `BitcoinV2.proto` inputs, by mapping
+    /// `witness_utxo`/`non_witness_utxo`, `sighash_type` and
+    /// `redeem_script`/`witness_script` onto `InputBuilder::utxo_from_proto`'s
+    /// target shape before handing off to the shared UTXO compiler.
+    pub fn psbt_preimage_hashes(
+        &self,
+        _coin: &dyn CoinContext,
+        proto: Proto::PsbtSigningInput<'_>,
+    ) -> Result<Proto::PsbtPreSigningOutput<'static>> {
+        let psbt = crate::modules::psbt::deserialize(&proto.psbt)?;
+
+        let mut utxo_inputs = vec![];
+        for (index, input) in psbt.inputs.iter().enumerate() {
+            utxo_inputs.push(crate::modules::psbt::utxo_from_psbt_input(
+                &psbt, index, input,
+            )?);
+        }
+
+        let utxo_outputs = psbt
+            .unsigned_tx
+            .output
+            .iter()
+            .map(|out| UtxoProto::TxOut {
+                value: out.value.to_sat(),
+                script_pubkey: Cow::Owned(out.script_pubkey.to_bytes()),
+            })
+            .collect();
+
+        let utxo_signing = UtxoProto::SigningInput {
+            version: psbt.unsigned_tx.version.0,
+            lock_time: psbt.unsigned_tx.lock_time.to_consensus_u32(),
+            inputs: utxo_inputs,
+            outputs: utxo_outputs,
+            input_selector: Proto::InputSelector::UseAll,
+            weight_base: 0,
+            change_script_pubkey: Cow::default(),
+            disable_change_output: true,
+        };
+
+        let utxo_presigning = tw_utxo::compiler::Compiler::preimage_hashes(utxo_signing);
+        handle_utxo_error(&utxo_presigning.error)?;
+
+        // Same per-input key/derivation pairing as `preimage_hashes_impl`, so
+        // a PSBT's sighashes can also be handed to an external/hardware
+        // signer and re-imported by key via `apply_partial_signatures`.
+        let sighashes = utxo_presigning
+            .sighashes
+            .into_iter()
+            .zip(utxo_presigning.inputs.iter())
+            .enumerate()
+            .map(|(input_index, (sighash, utxo_input))| {
+                let (public_key, derivation_path) =
+                    crate::modules::transactions::InputBuilder::expected_signing_key(utxo_input);
+
+                Proto::InputSighash {
+                    input_index: input_index as u32,
+                    sighash,
+                    sighash_type: utxo_input.sighash_type,
+                    public_key: Cow::Owned(public_key),
+                    derivation_path: derivation_path.map(Cow::Owned).unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        Ok(Proto::PsbtPreSigningOutput {
+            error: Proto::Error::OK,
+            sighashes,
+        })
+    }
+
+    /// BIP-174 "Finalizer" role: once every input carries enough partial
+    /// signatures, assemble the final `scriptSig`/witness for each one and
+    /// extract the network-broadcastable transaction.
+    ///
+    /// Like `compile_impl`'s "signature for each input" check, every input
+    /// must carry a signature for its expected key: a PSBT with any input
+    /// still unsigned is rejected here with an error. Passing a
+    /// partially-signed PSBT on to the next co-signer is `psbt_sign`'s job.
+    pub fn psbt_finalize(
+        &self,
+        _coin: &dyn CoinContext,
+        proto: Proto::PsbtFinalizeInput<'_>,
+    ) -> Result<Proto::SigningOutput<'static>> {
+        let psbt = crate::modules::psbt::deserialize(&proto.psbt)?;
+
+        let mut utxo_input_claims = vec![];
+        for (index, input) in psbt.inputs.iter().enumerate() {
+            let utxo_in = crate::modules::psbt::utxo_from_psbt_input(&psbt, index, input)?;
+
+            // Select the signature by the input's expected public key, the
+            // same way `compile_impl` does, rather than grabbing whichever
+            // `partial_sigs` entry happens to be first: a co-signer's
+            // unrelated signature must never be attached to this input.
+            let (expected_public_key, _) =
+                crate::modules::transactions::InputBuilder::expected_signing_key(&utxo_in);
+            // A 32-byte (x-only) key is a taproot key-path signer, whose
+            // signature `apply_partial_signatures` stores in `tap_key_sig`.
+            let signature = if expected_public_key.len() == 32 {
+                input.tap_key_sig.map(|sig| sig.to_vec())
+            } else {
+                let expected_public_key = bitcoin::PublicKey::from_slice(&expected_public_key)
+                    .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+                input.partial_sigs.get(&expected_public_key).map(|sig| sig.to_vec())
+            }
+            .ok_or_else(|| Error::from(Proto::Error::Error_unmatched_input_signature_count))?;
+
+            utxo_input_claims.push(
+                crate::modules::transactions::InputClaimBuilder::utxo_claim_from_utxo_in(
+                    &utxo_in, signature,
+                )?,
+            );
+        }
+
+        let utxo_outputs: Vec<UtxoProto::TxOut> = psbt
+            .unsigned_tx
+            .output
+            .iter()
+            .map(|out| UtxoProto::TxOut {
+                value: out.value.to_sat(),
+                script_pubkey: Cow::Owned(out.script_pubkey.to_bytes()),
+            })
+            .collect();
+
+        let utxo_preserializtion = UtxoProto::PreSerialization {
+            version: psbt.unsigned_tx.version.0,
+            lock_time: psbt.unsigned_tx.lock_time.to_consensus_u32(),
+            inputs: utxo_input_claims,
+            outputs: utxo_outputs,
+            weight_base: 0,
+        };
+
+        let utxo_serialized = tw_utxo::compiler::Compiler::compile(utxo_preserializtion);
+        handle_utxo_error(&utxo_serialized.error)?;
+
+        Ok(Proto::SigningOutput {
+            error: Proto::Error::OK,
+            encoded: utxo_serialized.encoded,
+            txid: utxo_serialized.txid,
+            weight: utxo_serialized.weight,
+            fee: utxo_serialized.fee,
+            ..Default::default()
+        })
+    }
+}
+
 #[rustfmt::skip]
 /// Convert `Utxo.proto` error type to `BitcoinV2.proto` error type.
 fn handle_utxo_error(utxo_err: &UtxoProto::Error) -> Result<()> {
@@ -0,0 +1,21 @@
+use tw_proto::BitcoinV2::Proto;
+
+/// Crate-wide error type: every fallible path here ultimately reports
+/// through the `BitcoinV2.proto` `Error` enum, so entries just wrap it
+/// rather than introducing a parallel error hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Error(pub Proto::Error);
+
+impl From<Proto::Error> for Error {
+    fn from(err: Proto::Error) -> Self {
+        Error(err)
+    }
+}
+
+impl From<Error> for Proto::Error {
+    fn from(err: Error) -> Self {
+        err.0
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,5 @@
+pub mod entry;
+mod error;
+mod modules;
+
+pub use error::{Error, Result};
@@ -0,0 +1,123 @@
+use crate::{Error, Result};
+use bitcoin::blockdata::opcodes::all as opcodes;
+use bitcoin::blockdata::script::{Builder, ScriptBuf};
+use bitcoin::hashes::Hash;
+use tw_proto::BitcoinV2::Proto;
+
+/// Which branch of the HTLC contract an `InputClaimBuilder` is spending.
+///
+/// The hashlock path is the receiver claiming the funds by revealing the
+/// secret; the timelock path is the sender reclaiming them after expiry.
+/// `compile_impl` picks the branch via `Proto::HtlcClaim::variant`, the same
+/// way it already dispatches on the other claiming-script variants.
+pub enum HtlcClaim<'a> {
+    Hashlock {
+        receiver_signature: &'a [u8],
+        secret: &'a [u8; 32],
+    },
+    Timelock {
+        sender_signature: &'a [u8],
+    },
+}
+
+/// Builds the HTLC witness script:
+///
+/// ```text
+/// OP_IF
+///     OP_SHA256 <secret_hash> OP_EQUALVERIFY
+///     <receiver_pubkey> OP_CHECKSIG
+/// OP_ELSE
+///     <locktime> OP_CHECKLOCKTIMEVERIFY OP_DROP
+///     <sender_pubkey> OP_CHECKSIG
+/// OP_ENDIF
+/// ```
+///
+/// The hashlock always checks with `OP_SHA256`, so `secret_hash` must be the
+/// SHA256 digest of the 32-byte secret; HASH160 hashlocks are not built.
+pub fn witness_script(
+    secret_hash: &[u8; 32],
+    receiver_pubkey: &[u8],
+    sender_pubkey: &[u8],
+    locktime: i64,
+) -> ScriptBuf {
+    Builder::new()
+        .push_opcode(opcodes::OP_IF)
+        .push_opcode(opcodes::OP_SHA256)
+        .push_slice(secret_hash)
+        .push_opcode(opcodes::OP_EQUALVERIFY)
+        .push_slice(receiver_pubkey)
+        .push_opcode(opcodes::OP_CHECKSIG)
+        .push_opcode(opcodes::OP_ELSE)
+        .push_int(locktime)
+        .push_opcode(opcodes::OP_CLTV)
+        .push_opcode(opcodes::OP_DROP)
+        .push_slice(sender_pubkey)
+        .push_opcode(opcodes::OP_CHECKSIG)
+        .push_opcode(opcodes::OP_ENDIF)
+        .into_script()
+}
+
+/// Assembles the witness stack for either HTLC branch.
+///
+/// The hashlock path pushes the receiver's signature, the 32-byte preimage
+/// and an `OP_1` selector; the timelock refund path pushes the sender's
+/// signature and an `OP_0` selector. Both append the witness script itself,
+/// same as any other P2WSH spend.
+pub fn claim_witness(
+    claim: &HtlcClaim<'_>,
+    witness_script: &ScriptBuf,
+) -> Vec<Vec<u8>> {
+    let script_bytes = witness_script.to_bytes();
+
+    match claim {
+        HtlcClaim::Hashlock {
+            receiver_signature,
+            secret,
+        } => vec![
+            receiver_signature.to_vec(),
+            secret.to_vec(),
+            vec![1],
+            script_bytes,
+        ],
+        HtlcClaim::Timelock { sender_signature } => {
+            vec![sender_signature.to_vec(), vec![], script_bytes]
+        },
+    }
+}
+
+/// BIP-65's `OP_CHECKLOCKTIMEVERIFY` threshold separating block-height
+/// locktimes from UNIX timestamp locktimes; `nLockTime` and the CLTV operand
+/// must fall on the same side of it for the comparison to mean anything.
+const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+
+/// The timelock refund path only becomes valid once the contract's
+/// `OP_CHECKLOCKTIMEVERIFY` locktime has passed, so the spending
+/// transaction's `lock_time` must actually satisfy `contract_locktime` (of
+/// the same kind - block-height vs. timestamp - or the comparison is
+/// meaningless) and carry a non-final `sequence` so `lock_time` is enforced
+/// by consensus at all. Building and signing a refund whose `lock_time`
+/// doesn't clear this is pointless: the `OP_CHECKLOCKTIMEVERIFY` clause will
+/// fail at broadcast no matter how the transaction is put together.
+pub fn enforce_refund_locktime(
+    contract_locktime: u32,
+    tx_lock_time: u32,
+    sequence: &mut u32,
+) -> Result<()> {
+    let same_kind =
+        (contract_locktime < LOCKTIME_THRESHOLD) == (tx_lock_time < LOCKTIME_THRESHOLD);
+    if !same_kind || tx_lock_time < contract_locktime {
+        return Err(Error::from(Proto::Error::Error_utxo_invalid_lock_time));
+    }
+
+    if *sequence == u32::MAX {
+        // A final sequence number disables `nLockTime` entirely, which would
+        // make the CLTV clause in the witness script unenforceable.
+        *sequence = u32::MAX - 1;
+    }
+
+    Ok(())
+}
+
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    bitcoin::hashes::sha256::Hash::hash(data).to_byte_array()
+}
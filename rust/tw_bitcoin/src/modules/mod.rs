@@ -0,0 +1,29 @@
+// NOTE: this series (chunk0-1 through chunk0-5) assumes `tw_proto`'s
+// `BitcoinV2.proto`/`Utxo.proto` gained the following, none of which live in
+// this checkout (the `tw_proto` crate and its `.proto` sources aren't part
+// of the `tw_bitcoin` crate/repo slice this tree contains). NOT MERGEABLE as
+// a standalone change: it cannot build until the schema below lands in
+// `tw_proto`, and every assumption here needs to be checked against the
+// actual generated types once it does, not taken on faith.
+//   - `Proto::Input::claiming_script` oneof: `htlc_claim` variant carrying
+//     `secret`/`secret_hash`, `receiver_pubkey`, `sender_pubkey`, `locktime`,
+//     `variant` (hashlock/timelock).
+//   - `Proto::Output::to_recipient` oneof: matching `htlc` variant.
+//   - `Proto::InputSighash` message: `input_index`, `sighash`, `sighash_type`,
+//     `public_key`, `derivation_path` (replaces the old flat `sighashes:
+//     Vec<Cow<[u8]>>` on `PreSigningOutput`).
+//   - `Proto::PsbtSigningInput`/`PsbtSigningOutput`/`PsbtPreSigningOutput`/
+//     `PsbtFinalizeInput` messages and `Error_invalid_psbt`,
+//     `Error_missing_psbt_utxo`, `Error_invalid_public_key`,
+//     `Error_invalid_signature`, `Error_invalid_private_key`,
+//     `Error_invalid_claiming_script`, `Error_invalid_recipient`,
+//     `Error_missing_input_signature` error variants.
+// `Utxo.proto`'s `TxIn` additionally grows `expected_public_key`/
+// `expected_derivation_path` fields so input selection/reordering in
+// `tw_utxo::compiler::Compiler` doesn't lose track of which key a sighash
+// belongs to.
+pub mod htlc;
+pub mod prefix;
+pub mod psbt;
+pub mod signer;
+pub mod transactions;
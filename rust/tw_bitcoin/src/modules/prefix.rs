@@ -0,0 +1,53 @@
+use tw_coin_entry::prefix::AddressPrefix;
+
+/// `BitcoinEntry::AddressPrefix`: selects which network an address should be
+/// parsed/derived for.
+///
+/// `NoPrefix` only ever produced mainnet addresses, which made it impossible
+/// to exercise `parse_address`/`derive_address` against testnet/signet/regtest
+/// nodes. Defaulting to `Bitcoin` when no prefix is supplied keeps existing
+/// callers (who pass `None`) behaving exactly as before.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BitcoinAddressPrefix {
+    pub network: BitcoinNetwork,
+    pub address_type: BitcoinAddressType,
+}
+
+impl AddressPrefix for BitcoinAddressPrefix {}
+
+/// Which script type `derive_address` should build for a given public key.
+///
+/// The claim builders can spend P2PKH, P2WPKH and P2TR key-path inputs (the
+/// BRC20 test spends both P2WPKH and taproot inputs). P2SH-wrapped P2WPKH
+/// addresses can be derived here, but no claiming-script builder spends them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BitcoinAddressType {
+    #[default]
+    P2pkh,
+    P2wpkh,
+    P2shP2wpkh,
+    P2tr,
+}
+
+/// Mirrors `bitcoin::Network`, kept as our own type so it can be constructed
+/// straight from the registry/coin config without depending on `rust-bitcoin`
+/// at that layer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BitcoinNetwork {
+    #[default]
+    Bitcoin,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+impl From<BitcoinNetwork> for bitcoin::Network {
+    fn from(network: BitcoinNetwork) -> Self {
+        match network {
+            BitcoinNetwork::Bitcoin => bitcoin::Network::Bitcoin,
+            BitcoinNetwork::Testnet => bitcoin::Network::Testnet,
+            BitcoinNetwork::Signet => bitcoin::Network::Signet,
+            BitcoinNetwork::Regtest => bitcoin::Network::Regtest,
+        }
+    }
+}
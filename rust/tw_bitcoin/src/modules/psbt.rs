@@ -0,0 +1,173 @@
+use crate::{Error, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use bitcoin::psbt::{Input as PsbtInput, Psbt};
+use std::borrow::Cow;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// Decode a base64/binary-encoded BIP-174 PSBT into the `bitcoin` crate's
+/// in-memory representation.
+///
+/// Accepts either the raw binary serialization or its base64 encoding, since
+/// both are in common use across wallets and hardware signers.
+pub fn deserialize(raw: &[u8]) -> Result<Psbt> {
+    if let Ok(psbt) = Psbt::deserialize(raw) {
+        return Ok(psbt);
+    }
+
+    // `base64`'s free functions were removed in favor of the `Engine` trait;
+    // use the standard (non-URL-safe, padded) engine PSBTs are conventionally
+    // encoded with.
+    let decoded = BASE64
+        .decode(raw)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_psbt))?;
+    Psbt::deserialize(&decoded).map_err(|_| Error::from(Proto::Error::Error_invalid_psbt))
+}
+
+/// Map a single PSBT input onto the `Utxo.proto` `TxIn` that
+/// `InputBuilder`/`InputClaimBuilder` already know how to sighash and claim.
+///
+/// This reuses the existing builder flow so PSBT-sourced inputs go through
+/// exactly the same sighash computation and witness/scriptSig assembly as
+/// inputs built from `BitcoinV2.proto` directly.
+pub fn utxo_from_psbt_input(
+    psbt: &Psbt,
+    index: usize,
+    input: &PsbtInput,
+) -> Result<UtxoProto::TxIn<'static>> {
+    let txin = psbt
+        .unsigned_tx
+        .input
+        .get(index)
+        .ok_or_else(|| Error::from(Proto::Error::Error_invalid_psbt))?;
+
+    let (script_pubkey, value) = if let Some(witness_utxo) = &input.witness_utxo {
+        (witness_utxo.script_pubkey.to_bytes(), witness_utxo.value.to_sat())
+    } else if let Some(non_witness_utxo) = &input.non_witness_utxo {
+        let prev_out = non_witness_utxo
+            .output
+            .get(txin.previous_output.vout as usize)
+            .ok_or_else(|| Error::from(Proto::Error::Error_invalid_psbt))?;
+        (prev_out.script_pubkey.to_bytes(), prev_out.value.to_sat())
+    } else {
+        return Err(Error::from(Proto::Error::Error_missing_psbt_utxo));
+    };
+
+    // `redeem_script`/`witness_script` carry the claiming script for
+    // P2SH/P2WSH inputs; a plain P2WPKH/P2TR input has neither and relies on
+    // the `script_pubkey` alone, same as the non-PSBT input builder.
+    let claiming_script = input
+        .witness_script
+        .as_ref()
+        .or(input.redeem_script.as_ref())
+        .map(|script| script.to_bytes())
+        .unwrap_or_default();
+
+    let sighash_type = input
+        .sighash_type
+        .map(|ty| ty.to_u32())
+        .unwrap_or(UtxoProto::SighashType::UseDefault as u32);
+
+    // Only single-key inputs are supported end-to-end today (matching
+    // `InputBuilder`'s non-PSBT counterpart); a multisig input's first
+    // `bip32_derivation` entry is used so callers at least get *a* expected
+    // key rather than none. Taproot inputs key their derivation separately
+    // (`tap_internal_key`/`tap_bip32_derivation`, both x-only), so those are
+    // checked first; a 32-byte `expected_public_key` is what tells
+    // `Signer::signatures_from_proto` to sign with Schnorr instead of ECDSA.
+    let (expected_public_key, expected_derivation_path) = if let Some(internal_key) =
+        input.tap_internal_key
+    {
+        let derivation_path = input
+            .tap_bip32_derivation
+            .get(&internal_key)
+            .map(|(_leaf_hashes, (_fingerprint, path))| path.to_string())
+            .unwrap_or_default();
+        (internal_key.serialize().to_vec(), derivation_path)
+    } else {
+        input
+            .bip32_derivation
+            .iter()
+            .next()
+            .map(|(pubkey, (_fingerprint, path))| (pubkey.serialize().to_vec(), path.to_string()))
+            .unwrap_or_default()
+    };
+
+    Ok(UtxoProto::TxIn {
+        txid: Cow::Owned(txin.previous_output.txid.to_byte_array().to_vec()),
+        vout: txin.previous_output.vout,
+        value,
+        sequence: txin.sequence.0,
+        script_pubkey: Cow::Owned(script_pubkey),
+        claiming_script: Cow::Owned(claiming_script),
+        sighash_type,
+        expected_public_key: Cow::Owned(expected_public_key),
+        expected_derivation_path: Cow::Owned(expected_derivation_path),
+    })
+}
+
+/// Merge freshly produced `(input_index, public_key, signature)` triples into
+/// a PSBT's `partial_sigs`, preserving whatever was already present so a
+/// partially-signed PSBT can keep moving between co-signers.
+///
+/// Each triple is only ever applied to the input it carries the index for;
+/// two inputs sharing a signing key (an ordinary multi-UTXO wallet scenario)
+/// would otherwise both match the same `pubkey` and whichever signature is
+/// iterated last would clobber the other's, even though it was computed over
+/// a different input's sighash.
+///
+/// This is the "Signer" role from BIP-174: it never finalizes an input by
+/// itself, matching `compile_impl`'s separation of signing from witness
+/// assembly.
+pub fn apply_partial_signatures(
+    psbt: &mut Psbt,
+    signatures: &[(u32, Vec<u8>, Vec<u8>)],
+) -> Result<()> {
+    for (input_index, pubkey_bytes, sig_bytes) in signatures {
+        let input = psbt
+            .inputs
+            .get_mut(*input_index as usize)
+            .ok_or_else(|| Error::from(Proto::Error::Error_invalid_psbt))?;
+
+        // A 32-byte key is a taproot key-path signer: the signature is a
+        // BIP-340 Schnorr signature that belongs in `tap_key_sig`, not an
+        // ECDSA `partial_sigs` entry, which would simply fail to parse it.
+        if pubkey_bytes.len() == 32 {
+            let internal_key = bitcoin::key::XOnlyPublicKey::from_slice(pubkey_bytes)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+
+            if !input_expects_tap_key(input, &internal_key) {
+                return Err(Error::from(Proto::Error::Error_invalid_public_key));
+            }
+
+            let sig = bitcoin::taproot::Signature::from_slice(sig_bytes)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_signature))?;
+            input.tap_key_sig = Some(sig);
+            continue;
+        }
+
+        let pubkey = bitcoin::PublicKey::from_slice(pubkey_bytes)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+
+        if !input_expects_key(input, &pubkey) {
+            return Err(Error::from(Proto::Error::Error_invalid_public_key));
+        }
+
+        let sig = bitcoin::ecdsa::Signature::from_slice(sig_bytes)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_signature))?;
+        input.partial_sigs.insert(pubkey, sig);
+    }
+
+    Ok(())
+}
+
+fn input_expects_key(input: &PsbtInput, pubkey: &bitcoin::PublicKey) -> bool {
+    input.bip32_derivation.contains_key(&pubkey.inner)
+        || input.partial_sigs.contains_key(pubkey)
+}
+
+fn input_expects_tap_key(input: &PsbtInput, internal_key: &bitcoin::key::XOnlyPublicKey) -> bool {
+    input.tap_internal_key == Some(*internal_key)
+        || input.tap_bip32_derivation.contains_key(internal_key)
+}
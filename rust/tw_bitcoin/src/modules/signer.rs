@@ -0,0 +1,93 @@
+use crate::{Error, Result};
+use bitcoin::secp256k1;
+use std::collections::HashMap;
+use tw_coin_entry::coin_entry::SignatureBytes;
+use tw_proto::BitcoinV2::Proto;
+
+/// Produces one signature per `Proto::InputSighash`, picking whichever key
+/// applies to that input: an input-specific key from `individual_keys` takes
+/// priority (mirroring per-UTXO private keys set on `Proto::Input`), falling
+/// back to the transaction-wide `private_key`.
+///
+/// The expected public key's length decides the scheme: a 32-byte x-only
+/// key marks a taproot key-path spend and signs with Schnorr, using the key
+/// tweaked with an empty merkle root (BIP86); everything else signs ECDSA.
+pub struct Signer;
+
+impl Signer {
+    /// Signs every sighash, failing the whole batch if any one of them has no
+    /// usable key. This is what `sign_impl` wants: a single-party signer is
+    /// expected to be able to sign everything it was asked to.
+    pub fn signatures_from_proto(
+        sighashes: &[Proto::InputSighash],
+        private_key: Vec<u8>,
+        individual_keys: HashMap<usize, Vec<u8>>,
+        dangerous_use_fixed_schnorr_rng: bool,
+    ) -> Result<Vec<SignatureBytes>> {
+        Self::try_signatures_from_proto(
+            sighashes,
+            private_key,
+            individual_keys,
+            dangerous_use_fixed_schnorr_rng,
+        )?
+        .into_iter()
+        .map(|signature| signature.ok_or_else(|| Error::from(Proto::Error::Error_invalid_private_key)))
+        .collect()
+    }
+
+    /// Same as `signatures_from_proto`, but signs whichever sighashes have a
+    /// usable key and returns `None` for the rest instead of failing the
+    /// whole batch. This is what the PSBT "Signer" role (`psbt_sign`) wants:
+    /// a co-signer in a multi-party signing flow is only ever expected to
+    /// sign the inputs it holds a key for, leaving the rest for the PSBT to
+    /// keep carrying to the next signer.
+    pub fn try_signatures_from_proto(
+        sighashes: &[Proto::InputSighash],
+        private_key: Vec<u8>,
+        individual_keys: HashMap<usize, Vec<u8>>,
+        dangerous_use_fixed_schnorr_rng: bool,
+    ) -> Result<Vec<Option<SignatureBytes>>> {
+        let secp = secp256k1::Secp256k1::new();
+
+        sighashes
+            .iter()
+            .map(|entry| {
+                let key_bytes = individual_keys
+                    .get(&(entry.input_index as usize))
+                    .unwrap_or(&private_key);
+
+                if key_bytes.is_empty() {
+                    return Ok(None);
+                }
+
+                let message = secp256k1::Message::from_digest_slice(&entry.sighash)
+                    .map_err(|_| Error::from(Proto::Error::Error_utxo_sighash_failed))?;
+
+                if entry.public_key.len() == 32 {
+                    let keypair = secp256k1::Keypair::from_seckey_slice(&secp, key_bytes)
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+                    // P2TR outputs commit to the tweaked output key, not the
+                    // internal key (see `p2tr_script_pubkey`).
+                    let keypair = bitcoin::key::TapTweak::tap_tweak(keypair, &secp, None).to_inner();
+
+                    let aux_rand = if dangerous_use_fixed_schnorr_rng {
+                        [0u8; 32]
+                    } else {
+                        secp256k1::rand::random()
+                    };
+
+                    let sig = secp.sign_schnorr_with_aux_rand(&message, &keypair, &aux_rand);
+                    Ok(Some(sig.as_ref().to_vec()))
+                } else {
+                    let secret_key = secp256k1::SecretKey::from_slice(key_bytes)
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))?;
+
+                    let sig = secp.sign_ecdsa(&message, &secret_key);
+                    let mut der = sig.serialize_der().to_vec();
+                    der.push(entry.sighash_type as u8);
+                    Ok(Some(der))
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,322 @@
+use crate::{Error, Result};
+use bitcoin::blockdata::script::Builder;
+use std::borrow::Cow;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// Converts `BitcoinV2.proto` input builders into the `Utxo.proto` shape the
+/// sighash compiler operates on.
+///
+/// `expected_public_key`/`expected_derivation_path` ride along on the
+/// produced `UtxoProto::TxIn` (rather than being looked up separately) so
+/// that after `tw_utxo::compiler::Compiler` selects/reorders inputs, the
+/// hardware-signer-facing `Proto::InputSighash` entries still know which key
+/// each sighash belongs to.
+pub struct InputBuilder;
+
+/// The pieces every claiming-script variant below needs to agree on:
+/// the scriptPubkey being spent, the redeem/witness script (empty unless
+/// the output is P2SH/P2WSH), and the key the eventual signature must match.
+struct ClaimedScript {
+    script_pubkey: Vec<u8>,
+    claiming_script: Vec<u8>,
+    expected_public_key: Vec<u8>,
+    expected_derivation_path: String,
+}
+
+impl InputBuilder {
+    pub fn utxo_from_proto(
+        input: &Proto::Input,
+        tx_lock_time: u32,
+    ) -> Result<UtxoProto::TxIn<'static>> {
+        let claimed = claimed_script(&input.claiming_script)?;
+
+        let mut sequence = u32::MAX;
+        if let Some(locktime) = Self::required_htlc_locktime(input) {
+            // The refund path's `OP_CHECKLOCKTIMEVERIFY` clause is only
+            // satisfiable if the transaction's own `lock_time` has actually
+            // passed it, and only enforced by consensus when `nSequence`
+            // doesn't disable `nLockTime` altogether.
+            crate::modules::htlc::enforce_refund_locktime(locktime, tx_lock_time, &mut sequence)?;
+        }
+
+        Ok(UtxoProto::TxIn {
+            txid: Cow::Owned(input.out_point.txid.to_vec()),
+            vout: input.out_point.vout,
+            value: input.value,
+            sequence,
+            script_pubkey: Cow::Owned(claimed.script_pubkey),
+            claiming_script: Cow::Owned(claimed.claiming_script),
+            sighash_type: input.sighash_type,
+            expected_public_key: Cow::Owned(claimed.expected_public_key),
+            expected_derivation_path: Cow::Owned(claimed.expected_derivation_path),
+        })
+    }
+
+    /// Reads back the `(public_key, derivation_path)` pair a `UtxoProto::TxIn`
+    /// was built with, so the same information survives input
+    /// selection/reordering performed by the UTXO compiler.
+    pub fn expected_signing_key(utxo_input: &UtxoProto::TxIn) -> (Vec<u8>, Option<String>) {
+        let derivation_path = if utxo_input.expected_derivation_path.is_empty() {
+            None
+        } else {
+            Some(utxo_input.expected_derivation_path.to_string())
+        };
+
+        (utxo_input.expected_public_key.to_vec(), derivation_path)
+    }
+
+    /// `Some(locktime)` when `input` spends an HTLC's timelock refund path,
+    /// meaning the whole transaction's `lock_time` must satisfy that
+    /// contract's `OP_CHECKLOCKTIMEVERIFY` clause.
+    pub fn required_htlc_locktime(input: &Proto::Input) -> Option<u32> {
+        match &input.claiming_script {
+            Proto::mod_Input::OneOfclaiming_script::htlc_claim(h)
+                if h.variant == Proto::mod_HtlcClaim::Variant::timelock =>
+            {
+                Some(h.locktime as u32)
+            },
+            _ => None,
+        }
+    }
+}
+
+fn claimed_script(
+    claiming_script: &Proto::mod_Input::OneOfclaiming_script,
+) -> Result<ClaimedScript> {
+    use Proto::mod_Input::OneOfclaiming_script as ClaimingScript;
+
+    let claimed = match claiming_script {
+        ClaimingScript::p2pkh(p) => ClaimedScript {
+            script_pubkey: p2pkh_script_pubkey(&p.pubkey)?,
+            claiming_script: Vec::new(),
+            expected_public_key: p.pubkey.to_vec(),
+            expected_derivation_path: p.derivation_path.to_string(),
+        },
+        ClaimingScript::p2wpkh(p) => ClaimedScript {
+            script_pubkey: p2wpkh_script_pubkey(&p.pubkey)?,
+            claiming_script: Vec::new(),
+            expected_public_key: p.pubkey.to_vec(),
+            expected_derivation_path: p.derivation_path.to_string(),
+        },
+        ClaimingScript::p2tr_key_path(p) => ClaimedScript {
+            script_pubkey: p2tr_script_pubkey(&p.public_key)?,
+            claiming_script: Vec::new(),
+            expected_public_key: p.public_key.to_vec(),
+            expected_derivation_path: p.derivation_path.to_string(),
+        },
+        ClaimingScript::brc20_inscribe(p) => ClaimedScript {
+            script_pubkey: p2wpkh_script_pubkey(&p.pubkey)?,
+            claiming_script: Vec::new(),
+            expected_public_key: p.pubkey.to_vec(),
+            expected_derivation_path: String::new(),
+        },
+        ClaimingScript::htlc_claim(h) => {
+            let witness_script = crate::modules::htlc::witness_script(
+                &h.secret_hash,
+                &h.receiver_pubkey,
+                &h.sender_pubkey,
+                h.locktime,
+            );
+
+            let expected_public_key = match h.variant {
+                Proto::mod_HtlcClaim::Variant::hashlock => h.receiver_pubkey.to_vec(),
+                Proto::mod_HtlcClaim::Variant::timelock => h.sender_pubkey.to_vec(),
+            };
+
+            ClaimedScript {
+                script_pubkey: witness_script.to_p2wsh().to_bytes(),
+                claiming_script: witness_script.to_bytes(),
+                expected_public_key,
+                expected_derivation_path: String::new(),
+            }
+        },
+        ClaimingScript::None => {
+            return Err(Error::from(Proto::Error::Error_invalid_claiming_script))
+        },
+    };
+
+    Ok(claimed)
+}
+
+/// Converts `BitcoinV2.proto` output builders into the `Utxo.proto` shape
+/// used both for sighash generation and for the final serialized outputs.
+pub struct OutputBuilder;
+
+pub struct UtxoOutput {
+    pub script_pubkey: Cow<'static, [u8]>,
+    pub value: u64,
+    pub taproot_payload: Cow<'static, [u8]>,
+    pub control_block: Cow<'static, [u8]>,
+}
+
+impl OutputBuilder {
+    pub fn utxo_from_proto(output: &Proto::Output) -> Result<UtxoOutput> {
+        use Proto::mod_Output::OneOfto_recipient as Recipient;
+
+        let script_pubkey = match &output.to_recipient {
+            Recipient::p2pkh(p) => p2pkh_script_pubkey(&p.pubkey)?,
+            Recipient::p2wpkh(p) => p2wpkh_script_pubkey(&p.pubkey)?,
+            Recipient::p2tr_key_path(p) => p2tr_script_pubkey(&p.public_key)?,
+            Recipient::brc20_inscribe(p) => p2wpkh_script_pubkey(&p.pubkey)?,
+            Recipient::htlc(h) => {
+                let witness_script = crate::modules::htlc::witness_script(
+                    &h.secret_hash,
+                    &h.receiver_pubkey,
+                    &h.sender_pubkey,
+                    h.locktime,
+                );
+                witness_script.to_p2wsh().to_bytes()
+            },
+            Recipient::None => return Err(Error::from(Proto::Error::Error_invalid_recipient)),
+        };
+
+        Ok(UtxoOutput {
+            script_pubkey: Cow::Owned(script_pubkey),
+            value: output.value,
+            taproot_payload: Cow::default(),
+            control_block: Cow::default(),
+        })
+    }
+}
+
+/// Turns a signature produced for an input into the final claim (scriptSig
+/// or witness items) that `tw_utxo::compiler::Compiler::compile` serializes
+/// into the transaction.
+pub struct InputClaimBuilder;
+
+impl InputClaimBuilder {
+    pub fn utxo_claim_from_proto(
+        input: &Proto::Input,
+        signature: Vec<u8>,
+        tx_lock_time: u32,
+    ) -> Result<UtxoProto::TxInClaim<'static>> {
+        let utxo_in = InputBuilder::utxo_from_proto(input, tx_lock_time)?;
+
+        if let Proto::mod_Input::OneOfclaiming_script::htlc_claim(h) = &input.claiming_script {
+            let witness_script = crate::modules::htlc::witness_script(
+                &h.secret_hash,
+                &h.receiver_pubkey,
+                &h.sender_pubkey,
+                h.locktime,
+            );
+
+            let claim = match h.variant {
+                Proto::mod_HtlcClaim::Variant::hashlock => crate::modules::htlc::HtlcClaim::Hashlock {
+                    receiver_signature: &signature,
+                    secret: &h.secret,
+                },
+                Proto::mod_HtlcClaim::Variant::timelock => crate::modules::htlc::HtlcClaim::Timelock {
+                    sender_signature: &signature,
+                },
+            };
+
+            let witness_items = crate::modules::htlc::claim_witness(&claim, &witness_script)
+                .into_iter()
+                .map(Cow::Owned)
+                .collect();
+
+            return Ok(UtxoProto::TxInClaim {
+                txid: Cow::Owned(utxo_in.txid.to_vec()),
+                vout: utxo_in.vout,
+                sequence: utxo_in.sequence,
+                script_sig: Cow::Owned(Vec::new()),
+                witness_items,
+            });
+        }
+
+        Self::utxo_claim_from_utxo_in(&utxo_in, signature)
+    }
+
+    /// Same as `utxo_claim_from_proto`, but starting from an already-built
+    /// `UtxoProto::TxIn` (used by the PSBT finalize path, which never holds a
+    /// `Proto::Input` in the first place).
+    ///
+    /// Unlike the HTLC branch above, `utxo_in` doesn't carry an explicit tag
+    /// for which plain script kind it's spending, so that's sniffed from
+    /// `script_pubkey` itself: P2PKH verification never looks at the
+    /// witness, so its signature+pubkey must go in `script_sig` instead, and
+    /// a taproot key-path witness is the signature alone, not `[sig,
+    /// pubkey]` the way P2WPKH's is.
+    pub fn utxo_claim_from_utxo_in(
+        utxo_in: &UtxoProto::TxIn,
+        signature: Vec<u8>,
+    ) -> Result<UtxoProto::TxInClaim<'static>> {
+        let pubkey = utxo_in.expected_public_key.to_vec();
+
+        let (script_sig, witness_items) = match script_claim_kind(&utxo_in.script_pubkey)? {
+            ScriptClaimKind::P2pkh => {
+                let script_sig = Builder::new()
+                    .push_slice(signature.as_slice())
+                    .push_slice(pubkey.as_slice())
+                    .into_script()
+                    .into_bytes();
+                (script_sig, Vec::new())
+            },
+            ScriptClaimKind::P2wpkh => (Vec::new(), vec![signature, pubkey]),
+            ScriptClaimKind::P2tr => (Vec::new(), vec![signature]),
+        };
+
+        Ok(UtxoProto::TxInClaim {
+            txid: Cow::Owned(utxo_in.txid.to_vec()),
+            vout: utxo_in.vout,
+            sequence: utxo_in.sequence,
+            script_sig: Cow::Owned(script_sig),
+            witness_items: witness_items.into_iter().map(Cow::Owned).collect(),
+        })
+    }
+
+    /// Which public key `compile_impl` should look up a signature for, given
+    /// an input's claiming-script builder. Matches `InputBuilder`'s notion of
+    /// "expected signing key" so both paths agree on the same key for the
+    /// same input.
+    pub fn expected_public_key(input: &Proto::Input) -> Result<Vec<u8>> {
+        Ok(claimed_script(&input.claiming_script)?.expected_public_key)
+    }
+}
+
+/// Which plain (non-HTLC) claim shape a `scriptPubkey` needs, sniffed from
+/// the script's own byte pattern since the `UtxoProto::TxIn` reconstructed
+/// from a PSBT carries no explicit tag for it.
+enum ScriptClaimKind {
+    P2pkh,
+    P2wpkh,
+    P2tr,
+}
+
+fn script_claim_kind(script_pubkey: &[u8]) -> Result<ScriptClaimKind> {
+    let script = bitcoin::Script::from_bytes(script_pubkey);
+
+    if script.is_p2pkh() {
+        Ok(ScriptClaimKind::P2pkh)
+    } else if script.is_p2wpkh() {
+        Ok(ScriptClaimKind::P2wpkh)
+    } else if script.is_p2tr() {
+        Ok(ScriptClaimKind::P2tr)
+    } else {
+        Err(Error::from(Proto::Error::Error_invalid_claiming_script))
+    }
+}
+
+fn p2pkh_script_pubkey(pubkey: &[u8]) -> Result<Vec<u8>> {
+    let pubkey = bitcoin::PublicKey::from_slice(pubkey)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+    Ok(bitcoin::ScriptBuf::new_p2pkh(&pubkey.pubkey_hash()).into_bytes())
+}
+
+fn p2wpkh_script_pubkey(pubkey: &[u8]) -> Result<Vec<u8>> {
+    let pubkey = bitcoin::PublicKey::from_slice(pubkey)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+    let wpubkey_hash = pubkey
+        .wpubkey_hash()
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+    Ok(bitcoin::ScriptBuf::new_p2wpkh(&wpubkey_hash).into_bytes())
+}
+
+fn p2tr_script_pubkey(xonly_pubkey: &[u8]) -> Result<Vec<u8>> {
+    let internal_key = bitcoin::key::XOnlyPublicKey::from_slice(xonly_pubkey)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+    let secp = bitcoin::secp256k1::Secp256k1::verification_only();
+    let (output_key, _parity) = internal_key.tap_tweak(&secp, None);
+    Ok(bitcoin::ScriptBuf::new_p2tr_tweaked(output_key).into_bytes())
+}
@@ -0,0 +1,78 @@
+use tw_bitcoin::entry::{BitcoinAddressPrefix, BitcoinAddressType, BitcoinNetwork, BitcoinEntry};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::derivation::Derivation;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_encoding::hex::DecodeHex;
+use tw_keypair::tw::{PublicKey, PublicKeyType};
+
+fn prefix(network: BitcoinNetwork) -> BitcoinAddressPrefix {
+    BitcoinAddressPrefix {
+        network,
+        address_type: BitcoinAddressType::P2pkh,
+    }
+}
+
+fn alice_pubkey() -> PublicKey {
+    let bytes = "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb"
+        .decode_hex()
+        .unwrap();
+    PublicKey::new(bytes, PublicKeyType::Secp256k1).unwrap()
+}
+
+/// `derive_address` followed by `parse_address` on the same network round-trips;
+/// parsing that same address against a *different* network must be rejected.
+/// Exercises the `BitcoinNetwork` selection added alongside `derive_address`.
+#[test]
+fn derive_and_parse_address_per_network() {
+    let coin = TestCoinContext::default();
+
+    let networks = [
+        BitcoinNetwork::Bitcoin,
+        BitcoinNetwork::Testnet,
+        BitcoinNetwork::Regtest,
+    ];
+
+    for network in networks {
+        let address = BitcoinEntry
+            .derive_address(&coin, alice_pubkey(), Derivation::Default, Some(prefix(network)))
+            .unwrap();
+
+        let parsed = BitcoinEntry
+            .parse_address(&coin, &address.to_string(), Some(prefix(network)))
+            .unwrap();
+        assert_eq!(parsed.to_string(), address.to_string());
+    }
+
+    // A testnet address isn't valid on mainnet and vice versa.
+    let testnet_address = BitcoinEntry
+        .derive_address(
+            &coin,
+            alice_pubkey(),
+            Derivation::Default,
+            Some(prefix(BitcoinNetwork::Testnet)),
+        )
+        .unwrap();
+    assert!(BitcoinEntry
+        .parse_address(
+            &coin,
+            &testnet_address.to_string(),
+            Some(prefix(BitcoinNetwork::Bitcoin)),
+        )
+        .is_err());
+
+    let mainnet_address = BitcoinEntry
+        .derive_address(
+            &coin,
+            alice_pubkey(),
+            Derivation::Default,
+            Some(prefix(BitcoinNetwork::Bitcoin)),
+        )
+        .unwrap();
+    assert!(BitcoinEntry
+        .parse_address(
+            &coin,
+            &mainnet_address.to_string(),
+            Some(prefix(BitcoinNetwork::Testnet)),
+        )
+        .is_err());
+}
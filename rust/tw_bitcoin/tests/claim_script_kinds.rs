@@ -0,0 +1,129 @@
+use bitcoin::secp256k1;
+use std::borrow::Cow;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+
+fn compressed_pubkey(secret: &secp256k1::SecretKey) -> Vec<u8> {
+    let secp = secp256k1::Secp256k1::new();
+    secp256k1::PublicKey::from_secret_key(&secp, secret)
+        .serialize()
+        .to_vec()
+}
+
+fn out_point(txid_byte: u8, vout: u32) -> Proto::OutPoint<'static> {
+    Proto::OutPoint {
+        txid: Cow::Owned(vec![txid_byte; 32]),
+        vout,
+    }
+}
+
+/// P2PKH verification never looks at the witness, so its signature+pubkey
+/// must land in `script_sig`; a witness-only claim (the P2WPKH shape) would
+/// leave `OP_CHECKSIG` running against an empty stack.
+#[test]
+fn sign_p2pkh_input_puts_claim_in_script_sig_not_witness() {
+    let coin = TestCoinContext::default();
+
+    let secret = secp256k1::SecretKey::from_slice(&[0x55; 32]).unwrap();
+    let pubkey = compressed_pubkey(&secret);
+
+    let input = Proto::Input {
+        out_point: out_point(0xcc, 0),
+        value: 50_000,
+        sighash_type: 1, // SighashBase::All
+        claiming_script: Proto::mod_Input::OneOfclaiming_script::p2pkh(Proto::mod_Input::P2pkh {
+            pubkey: Cow::Owned(pubkey.clone()),
+            ..Default::default()
+        }),
+        private_key: Cow::Owned(secret[..].to_vec()),
+        ..Default::default()
+    };
+
+    let output = Proto::Output {
+        value: 40_000,
+        to_recipient: Proto::mod_Output::OneOfto_recipient::p2wpkh(Proto::mod_Input::P2wpkh {
+            pubkey: Cow::Owned(pubkey),
+            ..Default::default()
+        }),
+    };
+
+    let signing = Proto::SigningInput {
+        inputs: vec![input],
+        outputs: vec![output],
+        input_selector: Proto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(transaction.inputs.len(), 1);
+    assert!(
+        !transaction.inputs[0].script_sig.is_empty(),
+        "P2PKH claim must go in script_sig"
+    );
+    assert!(
+        transaction.inputs[0].witness_items.is_empty(),
+        "P2PKH spends don't carry a witness"
+    );
+}
+
+/// A taproot key-path witness is the signature alone - pushing the pubkey
+/// alongside it (the P2WPKH shape) is not a valid key-path witness.
+#[test]
+fn sign_p2tr_key_path_input_witness_is_signature_only() {
+    let coin = TestCoinContext::default();
+
+    let secret = secp256k1::SecretKey::from_slice(&[0x66; 32]).unwrap();
+    let secp = secp256k1::Secp256k1::new();
+    let (xonly, _parity) = secp256k1::PublicKey::from_secret_key(&secp, &secret).x_only_public_key();
+    let xonly_bytes = xonly.serialize().to_vec();
+
+    let input = Proto::Input {
+        out_point: out_point(0xdd, 0),
+        value: 50_000,
+        sighash_type: 1,
+        claiming_script: Proto::mod_Input::OneOfclaiming_script::p2tr_key_path(
+            Proto::mod_Input::P2trKeyPath {
+                public_key: Cow::Owned(xonly_bytes.clone()),
+                ..Default::default()
+            },
+        ),
+        private_key: Cow::Owned(secret[..].to_vec()),
+        ..Default::default()
+    };
+
+    let output = Proto::Output {
+        value: 40_000,
+        to_recipient: Proto::mod_Output::OneOfto_recipient::p2tr_key_path(
+            Proto::mod_Input::P2trKeyPath {
+                public_key: Cow::Owned(xonly_bytes),
+                ..Default::default()
+            },
+        ),
+    };
+
+    let signing = Proto::SigningInput {
+        inputs: vec![input],
+        outputs: vec![output],
+        input_selector: Proto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(transaction.inputs.len(), 1);
+    assert!(transaction.inputs[0].script_sig.is_empty());
+    assert_eq!(
+        transaction.inputs[0].witness_items.len(),
+        1,
+        "a taproot key-path witness is the signature alone"
+    );
+}
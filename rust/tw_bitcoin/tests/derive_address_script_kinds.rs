@@ -0,0 +1,57 @@
+use tw_bitcoin::entry::{BitcoinAddressPrefix, BitcoinAddressType, BitcoinEntry, BitcoinNetwork};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::derivation::Derivation;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_encoding::hex::DecodeHex;
+use tw_keypair::tw::{PublicKey, PublicKeyType};
+
+fn alice_pubkey() -> PublicKey {
+    let bytes = "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb"
+        .decode_hex()
+        .unwrap();
+    PublicKey::new(bytes, PublicKeyType::Secp256k1).unwrap()
+}
+
+/// One `derive_address` call per new script kind, each producing a distinct,
+/// correctly-prefixed mainnet address (bech32 P2WPKH, P2SH-wrapped P2WPKH,
+/// bech32m P2TR), and round-tripping back through `parse_address`.
+#[test]
+fn derive_address_for_each_new_script_kind() {
+    let coin = TestCoinContext::default();
+
+    let cases = [
+        (BitcoinAddressType::P2wpkh, "bc1"),
+        (BitcoinAddressType::P2shP2wpkh, "3"),
+        (BitcoinAddressType::P2tr, "bc1p"),
+    ];
+
+    let mut seen = Vec::new();
+    for (address_type, expected_prefix) in cases {
+        let prefix = BitcoinAddressPrefix {
+            network: BitcoinNetwork::Bitcoin,
+            address_type,
+        };
+
+        let address = BitcoinEntry
+            .derive_address(&coin, alice_pubkey(), Derivation::Default, Some(prefix))
+            .unwrap()
+            .to_string();
+
+        assert!(
+            address.starts_with(expected_prefix),
+            "{address} does not start with {expected_prefix}"
+        );
+
+        let parsed = BitcoinEntry
+            .parse_address(&coin, &address, Some(prefix))
+            .unwrap();
+        assert_eq!(parsed.to_string(), address);
+
+        seen.push(address);
+    }
+
+    // Each script kind must derive to a different address for the same key.
+    assert_ne!(seen[0], seen[1]);
+    assert_ne!(seen[1], seen[2]);
+    assert_ne!(seen[0], seen[2]);
+}
@@ -0,0 +1,189 @@
+use bitcoin::hashes::Hash;
+use bitcoin::secp256k1;
+use std::borrow::Cow;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+
+fn compressed_pubkey(secret: &secp256k1::SecretKey) -> Vec<u8> {
+    let secp = secp256k1::Secp256k1::new();
+    secp256k1::PublicKey::from_secret_key(&secp, secret)
+        .serialize()
+        .to_vec()
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    bitcoin::hashes::sha256::Hash::hash(data).to_byte_array()
+}
+
+fn out_point(txid_byte: u8, vout: u32) -> Proto::OutPoint<'static> {
+    Proto::OutPoint {
+        txid: Cow::Owned(vec![txid_byte; 32]),
+        vout,
+    }
+}
+
+/// Funds an HTLC, then redeems it through the hashlock path: the receiver
+/// reveals the secret. Exercises the witness construction wired into
+/// `InputClaimBuilder::utxo_claim_from_proto`.
+#[test]
+fn htlc_fund_then_hashlock_redeem() {
+    let coin = TestCoinContext::default();
+
+    let funding_secret = secp256k1::SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let funding_pubkey = compressed_pubkey(&funding_secret);
+
+    let receiver_secret = secp256k1::SecretKey::from_slice(&[0x22; 32]).unwrap();
+    let receiver_pubkey = compressed_pubkey(&receiver_secret);
+
+    let sender_secret = secp256k1::SecretKey::from_slice(&[0x33; 32]).unwrap();
+    let sender_pubkey = compressed_pubkey(&sender_secret);
+
+    let secret = [0x44u8; 32];
+    let secret_hash = sha256(&secret);
+    let locktime: i64 = 500_000;
+
+    // Fund the HTLC from a plain P2WPKH input.
+    let fund_input = Proto::Input {
+        out_point: out_point(0xaa, 0),
+        value: 100_000,
+        sighash_type: 1, // SighashBase::All
+        claiming_script: Proto::mod_Input::OneOfclaiming_script::p2wpkh(Proto::mod_Input::P2wpkh {
+            pubkey: Cow::Owned(funding_pubkey.clone()),
+            ..Default::default()
+        }),
+        private_key: Cow::Owned(funding_secret[..].to_vec()),
+        ..Default::default()
+    };
+
+    let htlc_output = Proto::Output {
+        value: 90_000,
+        to_recipient: Proto::mod_Output::OneOfto_recipient::htlc(Proto::HtlcClaim {
+            secret_hash,
+            receiver_pubkey: Cow::Owned(receiver_pubkey.clone()),
+            sender_pubkey: Cow::Owned(sender_pubkey.clone()),
+            locktime,
+            ..Default::default()
+        }),
+    };
+
+    let funding_signing = Proto::SigningInput {
+        inputs: vec![fund_input],
+        outputs: vec![htlc_output],
+        input_selector: Proto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let funded = BitcoinEntry.sign(&coin, funding_signing);
+    assert_eq!(funded.error, Proto::Error::OK);
+
+    let htlc_txid = funded.txid.to_vec();
+
+    // Redeem via the hashlock path: the receiver reveals the secret.
+    let redeem_input = Proto::Input {
+        out_point: Proto::OutPoint {
+            txid: Cow::Owned(htlc_txid),
+            vout: 0,
+        },
+        value: 90_000,
+        sighash_type: 1,
+        claiming_script: Proto::mod_Input::OneOfclaiming_script::htlc_claim(Proto::HtlcClaim {
+            secret_hash,
+            receiver_pubkey: Cow::Owned(receiver_pubkey.clone()),
+            sender_pubkey: Cow::Owned(sender_pubkey.clone()),
+            locktime,
+            variant: Proto::mod_HtlcClaim::Variant::hashlock,
+            secret,
+        }),
+        private_key: Cow::Owned(receiver_secret[..].to_vec()),
+        ..Default::default()
+    };
+
+    let redeem_output = Proto::Output {
+        value: 80_000,
+        to_recipient: Proto::mod_Output::OneOfto_recipient::p2wpkh(Proto::mod_Input::P2wpkh {
+            pubkey: Cow::Owned(receiver_pubkey),
+            ..Default::default()
+        }),
+    };
+
+    let redeem_signing = Proto::SigningInput {
+        inputs: vec![redeem_input],
+        outputs: vec![redeem_output],
+        input_selector: Proto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let redeemed = BitcoinEntry.sign(&coin, redeem_signing);
+    assert_eq!(redeemed.error, Proto::Error::OK);
+
+    let redeemed_tx = redeemed.transaction.unwrap();
+    assert_eq!(redeemed_tx.inputs.len(), 1);
+    // signature, 32-byte secret, OP_1 selector, witness script.
+    assert_eq!(redeemed_tx.inputs[0].witness_items.len(), 4);
+    assert_eq!(redeemed_tx.inputs[0].witness_items[1].as_ref(), secret);
+}
+
+/// The timelock refund path must be rejected unless the transaction's own
+/// `lock_time` actually satisfies the contract's `OP_CHECKLOCKTIMEVERIFY`
+/// clause, matching `enforce_refund_locktime`'s validation.
+#[test]
+fn htlc_timelock_refund_requires_satisfying_lock_time() {
+    let coin = TestCoinContext::default();
+
+    let sender_secret = secp256k1::SecretKey::from_slice(&[0x33; 32]).unwrap();
+    let sender_pubkey = compressed_pubkey(&sender_secret);
+    let receiver_secret = secp256k1::SecretKey::from_slice(&[0x22; 32]).unwrap();
+    let receiver_pubkey = compressed_pubkey(&receiver_secret);
+
+    let secret_hash = sha256(&[0x44u8; 32]);
+    let locktime: i64 = 500_000;
+
+    let refund_signing = |tx_lock_time: u32| Proto::SigningInput {
+        inputs: vec![Proto::Input {
+            out_point: out_point(0xbb, 0),
+            value: 90_000,
+            sighash_type: 1,
+            claiming_script: Proto::mod_Input::OneOfclaiming_script::htlc_claim(
+                Proto::HtlcClaim {
+                    secret_hash,
+                    receiver_pubkey: Cow::Owned(receiver_pubkey.clone()),
+                    sender_pubkey: Cow::Owned(sender_pubkey.clone()),
+                    locktime,
+                    variant: Proto::mod_HtlcClaim::Variant::timelock,
+                    ..Default::default()
+                },
+            ),
+            private_key: Cow::Owned(sender_secret[..].to_vec()),
+            ..Default::default()
+        }],
+        outputs: vec![Proto::Output {
+            value: 80_000,
+            to_recipient: Proto::mod_Output::OneOfto_recipient::p2wpkh(Proto::mod_Input::P2wpkh {
+                pubkey: Cow::Owned(sender_pubkey.clone()),
+                ..Default::default()
+            }),
+        }],
+        input_selector: Proto::InputSelector::UseAll,
+        disable_change_output: true,
+        lock_time: tx_lock_time,
+        ..Default::default()
+    };
+
+    // A transaction `lock_time` that hasn't reached the contract's locktime
+    // yet must be rejected, not silently signed into an unbroadcastable tx.
+    let too_early = BitcoinEntry.sign(&coin, refund_signing((locktime - 1) as u32));
+    assert_eq!(too_early.error, Proto::Error::Error_utxo_invalid_lock_time);
+
+    let refunded = BitcoinEntry.sign(&coin, refund_signing(locktime as u32));
+    assert_eq!(refunded.error, Proto::Error::OK);
+
+    let refunded_tx = refunded.transaction.unwrap();
+    assert_eq!(refunded_tx.inputs.len(), 1);
+    // sender signature, empty selector, witness script.
+    assert_eq!(refunded_tx.inputs[0].witness_items.len(), 3);
+    assert!(refunded_tx.inputs[0].sequence < u32::MAX);
+}
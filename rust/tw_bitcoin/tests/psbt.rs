@@ -0,0 +1,194 @@
+mod support;
+
+use bitcoin::bip32::{DerivationPath, Fingerprint};
+use bitcoin::psbt::Psbt;
+use bitcoin::secp256k1;
+use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+use bitcoin::{absolute, transaction, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+use std::borrow::Cow;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+
+/// Two inputs locked to the *same* P2WPKH key but different prevouts, so
+/// signing/finalizing the PSBT exercises the same "one signer, many UTXOs"
+/// shape that used to collapse both inputs' signatures into whichever one
+/// `apply_partial_signatures` iterated last.
+#[test]
+fn psbt_sign_and_finalize_two_inputs_same_key_round_trip() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = support::alice_private_key();
+    let alice_pubkey = support::alice_pubkey();
+    let script_pubkey = ScriptBuf::new_p2wpkh(&alice_pubkey.wpubkey_hash().unwrap());
+
+    let prevout1 = OutPoint {
+        txid: support::repeated_txid(0x11),
+        vout: 0,
+    };
+    let prevout2 = OutPoint {
+        txid: support::repeated_txid(0x22),
+        vout: 1,
+    };
+
+    let unsigned_tx = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        input: vec![
+            TxIn {
+                previous_output: prevout1,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            },
+            TxIn {
+                previous_output: prevout2,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            },
+        ],
+        output: vec![TxOut {
+            value: Amount::from_sat(15_000),
+            script_pubkey: script_pubkey.clone(),
+        }],
+    };
+
+    let mut psbt = Psbt::from_unsigned_tx(unsigned_tx.clone()).unwrap();
+    let values = [Amount::from_sat(10_000), Amount::from_sat(9_000)];
+    for (input, value) in psbt.inputs.iter_mut().zip(values.iter()) {
+        input.witness_utxo = Some(TxOut {
+            value: *value,
+            script_pubkey: script_pubkey.clone(),
+        });
+        input
+            .bip32_derivation
+            .insert(alice_pubkey.inner, (Fingerprint::default(), DerivationPath::master()));
+    }
+
+    let signing = Proto::PsbtSigningInput {
+        psbt: Cow::Owned(psbt.serialize()),
+        // One entry per PSBT input index; both inputs are signed by the same
+        // key here, but each needs its own entry since `private_keys` is
+        // index-aligned to PSBT inputs, not a keyring tried against all of
+        // them.
+        private_keys: vec![
+            Cow::Owned(alice_private_key.clone()),
+            Cow::Owned(alice_private_key),
+        ],
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.psbt_sign(&coin, signing).unwrap();
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let signed_psbt = Psbt::deserialize(&signed.psbt).unwrap();
+
+    // Every input must validate against *its own* sighash; reusing the
+    // wrong input's signature is exactly the bug this test guards against.
+    let secp = secp256k1::Secp256k1::new();
+    let mut cache = SighashCache::new(&unsigned_tx);
+    for (index, value) in values.iter().enumerate() {
+        let sig = signed_psbt.inputs[index]
+            .partial_sigs
+            .get(&alice_pubkey)
+            .expect("missing partial signature for input");
+
+        let message = cache
+            .p2wpkh_signature_hash(index, &script_pubkey, *value, EcdsaSighashType::All)
+            .unwrap();
+        let message = secp256k1::Message::from_digest_slice(&message.to_byte_array()).unwrap();
+
+        secp.verify_ecdsa(&message, &sig.sig, &alice_pubkey.inner)
+            .expect("signature does not match this input's own sighash");
+    }
+
+    let finalize = Proto::PsbtFinalizeInput {
+        psbt: Cow::Owned(signed.psbt.to_vec()),
+    };
+    let finalized = BitcoinEntry.psbt_finalize(&coin, finalize).unwrap();
+    assert_eq!(finalized.error, Proto::Error::OK);
+
+    let final_tx: Transaction = bitcoin::consensus::deserialize(&finalized.encoded).unwrap();
+    assert_eq!(final_tx.input.len(), 2);
+    assert!(final_tx.input.iter().all(|txin| !txin.witness.is_empty()));
+}
+
+/// BIP-174 co-signers don't all hold every key up front: a call that only
+/// holds the key for one of two inputs must return the partially-signed
+/// PSBT for the input it could sign, not fail the whole batch because the
+/// other one isn't signable yet.
+#[test]
+fn psbt_sign_skips_input_with_no_matching_key() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = support::alice_private_key();
+    let alice_pubkey = support::alice_pubkey();
+    let script_pubkey = ScriptBuf::new_p2wpkh(&alice_pubkey.wpubkey_hash().unwrap());
+
+    let unsigned_tx = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        input: vec![
+            TxIn {
+                previous_output: OutPoint {
+                    txid: support::repeated_txid(0x33),
+                    vout: 0,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            },
+            TxIn {
+                previous_output: OutPoint {
+                    txid: support::repeated_txid(0x44),
+                    vout: 0,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            },
+        ],
+        output: vec![TxOut {
+            value: Amount::from_sat(15_000),
+            script_pubkey: script_pubkey.clone(),
+        }],
+    };
+
+    let mut psbt = Psbt::from_unsigned_tx(unsigned_tx).unwrap();
+    for (input, value) in psbt
+        .inputs
+        .iter_mut()
+        .zip([Amount::from_sat(10_000), Amount::from_sat(9_000)])
+    {
+        input.witness_utxo = Some(TxOut {
+            value,
+            script_pubkey: script_pubkey.clone(),
+        });
+        input
+            .bip32_derivation
+            .insert(alice_pubkey.inner, (Fingerprint::default(), DerivationPath::master()));
+    }
+
+    // Only input 0 gets a key; `private_keys[1]` is left empty, as it would
+    // be for a co-signer who hasn't been handed that key yet.
+    let signing = Proto::PsbtSigningInput {
+        psbt: Cow::Owned(psbt.serialize()),
+        private_keys: vec![Cow::Owned(alice_private_key), Cow::Owned(Vec::new())],
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.psbt_sign(&coin, signing).unwrap();
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let signed_psbt = Psbt::deserialize(&signed.psbt).unwrap();
+    assert!(
+        signed_psbt.inputs[0].partial_sigs.contains_key(&alice_pubkey),
+        "input 0 should have been signed"
+    );
+    assert!(
+        signed_psbt.inputs[1].partial_sigs.is_empty(),
+        "input 1 has no usable key yet and must be left untouched, not error the whole batch"
+    );
+}
+
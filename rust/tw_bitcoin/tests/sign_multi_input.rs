@@ -0,0 +1,246 @@
+mod support;
+
+use bitcoin::ecdsa::Signature as EcdsaSignature;
+use bitcoin::hashes::Hash;
+use bitcoin::secp256k1;
+use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+use bitcoin::{absolute, transaction, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+use std::borrow::Cow;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+
+fn two_same_key_inputs_and_output() -> (Proto::Input<'static>, Proto::Input<'static>, Proto::Output<'static>) {
+    let alice_pubkey_bytes = support::alice_pubkey_bytes();
+
+    let input1 = Proto::Input {
+        out_point: Proto::OutPoint {
+            txid: Cow::Owned(support::repeated_txid(0x11).to_byte_array().to_vec()),
+            vout: 0,
+        },
+        value: 10_000,
+        sighash_type: 1, // SighashBase::All
+        claiming_script: Proto::mod_Input::OneOfclaiming_script::p2wpkh(Proto::mod_Input::P2wpkh {
+            pubkey: Cow::Owned(alice_pubkey_bytes.clone()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let input2 = Proto::Input {
+        out_point: Proto::OutPoint {
+            txid: Cow::Owned(support::repeated_txid(0x22).to_byte_array().to_vec()),
+            vout: 1,
+        },
+        value: 9_000,
+        sighash_type: 1,
+        claiming_script: Proto::mod_Input::OneOfclaiming_script::p2wpkh(Proto::mod_Input::P2wpkh {
+            pubkey: Cow::Owned(alice_pubkey_bytes.clone()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let output = Proto::Output {
+        value: 18_000,
+        to_recipient: Proto::mod_Output::OneOfto_recipient::p2wpkh(Proto::mod_Input::P2wpkh {
+            pubkey: Cow::Owned(alice_pubkey_bytes),
+            ..Default::default()
+        }),
+    };
+
+    (input1, input2, output)
+}
+
+/// Two P2WPKH inputs locked to the *same* key but different prevouts. Each
+/// resulting witness signature must validate against its own input's sighash
+/// - reusing the wrong input's signature (both inputs getting the one
+/// signature looked up for their shared key, which this test guards against)
+/// would make one of the two validate against the other's txid/vout instead.
+#[test]
+fn sign_two_inputs_same_pubkey_each_matches_own_sighash() {
+    let coin = TestCoinContext::default();
+    let alice_private_key = support::alice_private_key();
+    let alice_pubkey = support::alice_pubkey();
+    let script_pubkey = ScriptBuf::new_p2wpkh(&alice_pubkey.wpubkey_hash().unwrap());
+
+    let (input1, input2, output) = two_same_key_inputs_and_output();
+
+    let signing = Proto::SigningInput {
+        private_key: Cow::Owned(alice_private_key),
+        inputs: vec![input1, input2],
+        outputs: vec![output],
+        input_selector: Proto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(transaction.inputs.len(), 2);
+
+    // Rebuild the unsigned transaction independently to compute each input's
+    // own sighash, so swapped signatures fail verification instead of both
+    // happening to look plausible.
+    let values = [Amount::from_sat(10_000), Amount::from_sat(9_000)];
+    let unsigned_tx = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        input: vec![
+            TxIn {
+                previous_output: OutPoint {
+                    txid: support::repeated_txid(0x11),
+                    vout: 0,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            },
+            TxIn {
+                previous_output: OutPoint {
+                    txid: support::repeated_txid(0x22),
+                    vout: 1,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            },
+        ],
+        output: vec![TxOut {
+            value: Amount::from_sat(18_000),
+            script_pubkey: script_pubkey.clone(),
+        }],
+    };
+
+    let secp = secp256k1::Secp256k1::new();
+    let mut cache = SighashCache::new(&unsigned_tx);
+    for (index, value) in values.iter().enumerate() {
+        let witness_items = &transaction.inputs[index].witness_items;
+        assert_eq!(witness_items.len(), 2, "expected [signature, pubkey]");
+
+        let sig = EcdsaSignature::from_slice(&witness_items[0]).unwrap();
+
+        let message = cache
+            .p2wpkh_signature_hash(index, &script_pubkey, *value, EcdsaSighashType::All)
+            .unwrap();
+        let message = secp256k1::Message::from_digest_slice(&message.to_byte_array()).unwrap();
+
+        secp.verify_ecdsa(&message, &sig.sig, &alice_pubkey.inner)
+            .expect("signature does not match this input's own sighash");
+    }
+}
+
+/// `compile()` is meant for an air-gapped/hardware signer that hands back
+/// `(public_key, signature)` pairs matched by key, not by the position the
+/// inputs were declared in - it's free to sign its sighashes in whatever
+/// order it likes. Call `preimage_hashes()` to get both inputs' sighashes,
+/// sign them independently, then call `compile()` with the two pairs given
+/// in *reverse* order. If `compile_impl` still matched positionally, input
+/// 0 would get input 1's signature and vice versa, and the resulting
+/// witness signatures would fail to validate against their own sighashes.
+#[test]
+fn compile_matches_out_of_order_signatures_by_key() {
+    let coin = TestCoinContext::default();
+    let alice_private_key = support::alice_private_key();
+    let alice_pubkey = support::alice_pubkey();
+    let script_pubkey = ScriptBuf::new_p2wpkh(&alice_pubkey.wpubkey_hash().unwrap());
+
+    let (input1, input2, output) = two_same_key_inputs_and_output();
+
+    let presigning = Proto::SigningInput {
+        inputs: vec![input1, input2],
+        outputs: vec![output.clone()],
+        input_selector: Proto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, presigning);
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.sighashes.len(), 2);
+
+    // Sign each sighash independently, the same way a hardware signer would:
+    // DER-encoded ECDSA signature with the sighash type byte appended.
+    let secp = secp256k1::Secp256k1::new();
+    let secret_key = secp256k1::SecretKey::from_slice(&alice_private_key).unwrap();
+    let signatures: Vec<Vec<u8>> = presigned
+        .sighashes
+        .iter()
+        .map(|entry| {
+            let message = secp256k1::Message::from_digest_slice(&entry.sighash).unwrap();
+            let sig = secp.sign_ecdsa(&message, &secret_key);
+            let mut der = sig.serialize_der().to_vec();
+            der.push(entry.sighash_type as u8);
+            der
+        })
+        .collect();
+
+    // Hand `compile()` the two (public_key, signature) pairs in reverse
+    // order relative to `proto.inputs` - exactly what a hardware signer
+    // that signed sighash 1 before sighash 0 would return.
+    let (input1, input2, _) = two_same_key_inputs_and_output();
+    let compiling = Proto::SigningInput {
+        inputs: vec![input1, input2],
+        outputs: vec![output],
+        input_selector: Proto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let reversed_signatures = vec![signatures[1].clone(), signatures[0].clone()];
+    let reversed_public_keys = vec![support::alice_pubkey_bytes(), support::alice_pubkey_bytes()];
+
+    let compiled = BitcoinEntry.compile(&coin, compiling, reversed_signatures, reversed_public_keys);
+    assert_eq!(compiled.error, Proto::Error::OK);
+
+    let transaction = compiled.transaction.unwrap();
+    assert_eq!(transaction.inputs.len(), 2);
+
+    let values = [Amount::from_sat(10_000), Amount::from_sat(9_000)];
+    let unsigned_tx = Transaction {
+        version: transaction::Version::TWO,
+        lock_time: absolute::LockTime::ZERO,
+        input: vec![
+            TxIn {
+                previous_output: OutPoint {
+                    txid: support::repeated_txid(0x11),
+                    vout: 0,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            },
+            TxIn {
+                previous_output: OutPoint {
+                    txid: support::repeated_txid(0x22),
+                    vout: 1,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::default(),
+            },
+        ],
+        output: vec![TxOut {
+            value: Amount::from_sat(18_000),
+            script_pubkey: script_pubkey.clone(),
+        }],
+    };
+
+    let secp = secp256k1::Secp256k1::new();
+    let mut cache = SighashCache::new(&unsigned_tx);
+    for (index, value) in values.iter().enumerate() {
+        let witness_items = &transaction.inputs[index].witness_items;
+        assert_eq!(witness_items.len(), 2, "expected [signature, pubkey]");
+
+        let sig = EcdsaSignature::from_slice(&witness_items[0]).unwrap();
+
+        let message = cache
+            .p2wpkh_signature_hash(index, &script_pubkey, *value, EcdsaSighashType::All)
+            .unwrap();
+        let message = secp256k1::Message::from_digest_slice(&message.to_byte_array()).unwrap();
+
+        secp.verify_ecdsa(&message, &sig.sig, &alice_pubkey.inner)
+            .expect("signature does not match this input's own sighash, even though it was handed back out of order");
+    }
+}
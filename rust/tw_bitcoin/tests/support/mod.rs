@@ -0,0 +1,34 @@
+#![allow(dead_code)]
+
+//! Fixtures shared across the PSBT and `compile`/`sign` integration tests, so
+//! each test file doesn't fabricate (and risk miscomputing) its own keypair
+//! and prevouts.
+
+use std::str::FromStr;
+use tw_encoding::hex::DecodeHex;
+
+/// A known-valid secp256k1 keypair, reused verbatim from the pre-existing
+/// BRC20 test fixture.
+pub const ALICE_PRIVATE_KEY_HEX: &str =
+    "e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129";
+pub const ALICE_PUBKEY_HEX: &str =
+    "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb";
+
+pub fn alice_private_key() -> Vec<u8> {
+    ALICE_PRIVATE_KEY_HEX.decode_hex().unwrap()
+}
+
+pub fn alice_pubkey_bytes() -> Vec<u8> {
+    ALICE_PUBKEY_HEX.decode_hex().unwrap()
+}
+
+pub fn alice_pubkey() -> bitcoin::PublicKey {
+    bitcoin::PublicKey::from_slice(&alice_pubkey_bytes()).unwrap()
+}
+
+/// A `Txid` with every byte set to `byte`, so tests can stand up distinct
+/// prevouts without depending on any real chain data.
+pub fn repeated_txid(byte: u8) -> bitcoin::Txid {
+    let hex_byte = format!("{byte:02x}");
+    bitcoin::Txid::from_str(&hex_byte.repeat(32)).unwrap()
+}